@@ -0,0 +1,97 @@
+use crate::geometry::*;
+use crate::vec::*;
+use rand::prelude::*;
+
+/// A hitable with no extent and no surface, used as the unused
+/// branch of a `BvhNode` leaf that holds a single object.
+struct EmptyHitable;
+
+impl Hitable for EmptyHitable {
+    fn hit(&self, _ray: &Ray, _t_min: Elem, _t_max: Elem) -> Option<HitRecord> {
+        None
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        None
+    }
+}
+
+/// A bounding-volume hierarchy over a set of hitables. Replaces the
+/// `impl Hitable for [T]` linear scan with an O(log n) tree search:
+/// each node first rejects the ray against its own box before
+/// recursing into its children.
+pub struct BvhNode {
+    left: Box<dyn Hitable + Send + Sync>,
+    right: Box<dyn Hitable + Send + Sync>,
+    bbox: Aabb,
+}
+
+impl BvhNode {
+    /// Builds a tree over `hitables`, consuming it. Every element
+    /// must have a bounding box (i.e. `bounding_box()` must not
+    /// return `None`); unbounded objects can't be placed in a BVH.
+    ///
+    /// `hitables` must not be empty: an empty slice has no split
+    /// point, so the recursive case below would call itself forever
+    /// on two empty halves.
+    pub fn new(mut hitables: Vec<Box<dyn Hitable + Send + Sync>>) -> BvhNode {
+        assert!(!hitables.is_empty(), "BvhNode::new: empty scene");
+
+        let axis = rand::thread_rng().gen_range(0..3);
+        hitables.sort_by(|a, b| {
+            let min_a = component(box_min(a), axis);
+            let min_b = component(box_min(b), axis);
+            min_a.partial_cmp(&min_b).unwrap()
+        });
+
+        let (left, right): (Box<dyn Hitable + Send + Sync>, Box<dyn Hitable + Send + Sync>) =
+            match hitables.len() {
+                1 => (hitables.pop().unwrap(), Box::new(EmptyHitable)),
+                2 => {
+                    let right = hitables.pop().unwrap();
+                    let left = hitables.pop().unwrap();
+                    (left, right)
+                }
+                len => {
+                    let right_half = hitables.split_off(len / 2);
+                    (
+                        Box::new(BvhNode::new(hitables)),
+                        Box::new(BvhNode::new(right_half)),
+                    )
+                }
+            };
+
+        let bbox = match (left.bounding_box(), right.bounding_box()) {
+            (Some(l), Some(r)) => Aabb::surrounding(&l, &r),
+            (Some(l), None) => l,
+            (None, Some(r)) => r,
+            (None, None) => panic!("BvhNode: hitable without a bounding box"),
+        };
+
+        BvhNode { left, right, bbox }
+    }
+}
+
+fn box_min(h: &(dyn Hitable + Send + Sync)) -> Vec3 {
+    h.bounding_box()
+        .expect("BvhNode: hitable without a bounding box")
+        .min
+}
+
+impl Hitable for BvhNode {
+    fn hit(&self, ray: &Ray, t_min: Elem, t_max: Elem) -> Option<HitRecord> {
+        if !self.bbox.hit(ray, t_min, t_max) {
+            return None;
+        }
+
+        let left_hit = self.left.hit(ray, t_min, t_max);
+        let right_t_max = left_hit.as_ref().map_or(t_max, |h| h.time);
+        let right_hit = self.right.hit(ray, t_min, right_t_max);
+
+        right_hit.or(left_hit)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(self.bbox)
+    }
+}
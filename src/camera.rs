@@ -1,7 +1,8 @@
 use crate::geometry::*;
 use crate::vec::*;
-use rand::prelude::*;
-use std::f32::consts::PI;
+use rand::{Rng, RngCore};
+use rand_distr::{Distribution, UnitDisc};
+use std::f64::consts::PI;
 
 /// Defines the screen on which scene is projected and the origin
 /// (i.e. the point of view).
@@ -13,6 +14,8 @@ pub struct Camera {
     u: Vec3,
     v: Vec3,
     lens_radius: Elem,
+    time0: Elem,
+    time1: Elem,
 }
 
 /// Camera settings used to define a Camera.
@@ -33,6 +36,10 @@ pub struct CameraSettings {
     pub aperture: Elem,
     /// distance to focus plance
     pub focus_dist: Elem,
+    /// shutter open time (used to sample ray time for motion blur)
+    pub time0: Elem,
+    /// shutter close time (used to sample ray time for motion blur)
+    pub time1: Elem,
 }
 
 impl Camera {
@@ -42,8 +49,8 @@ impl Camera {
         let half_width = s.aspect * half_height;
         let origin = s.look_from;
         let w = Vec3::unit_vector(s.look_from - s.look_at);
-        let u = Vec3::unit_vector(Vec3::cross(s.v_up, w));
-        let v = Vec3::cross(w, u);
+        let u = Vec3::unit_vector(s.v_up.cross(&w));
+        let v = w.cross(&u);
 
         Camera {
             origin,
@@ -53,29 +60,33 @@ impl Camera {
             lens_radius: s.aperture / 2.0,
             u,
             v,
+            time0: s.time0,
+            time1: s.time1,
         }
     }
 
     /// Calculates the ray to a particular point on the camera matrix,
-    /// specified by (s, t) coordinates.
-    pub fn get_ray(&self, s: Elem, t: Elem) -> Ray {
-        let rd = self.lens_radius * random_in_unit_disk();
+    /// specified by (s, t) coordinates. The ray's time is sampled
+    /// uniformly within the shutter interval `[time0, time1]`, which
+    /// is what lets moving objects blur across the frame. `rng` is
+    /// the caller's seedable random source, shared with the material
+    /// scatter methods so the camera's and the materials' sampling is
+    /// reproducible from a single seed.
+    pub fn get_ray(&self, s: Elem, t: Elem, rng: &mut dyn RngCore) -> Ray {
+        let rd = self.lens_radius * random_in_unit_disk(rng);
         let offset = self.u * rd.x() + self.v * rd.y();
+        let time = self.time0 + rng.gen::<Elem>() * (self.time1 - self.time0);
         Ray::new(
             self.origin + offset,
             self.lower_left_corner + s * self.horizontal + t * self.vertical - self.origin - offset,
+            time,
         )
     }
 }
 
-fn random_in_unit_disk() -> Vec3 {
-    let mut v;
-    let mut rng = rand::thread_rng();
-    loop {
-        v = 2.0 * Vec3(rng.gen(), rng.gen(), 0.0) - Vec3(1.0, 1.0, 0.0);
-        if v.length_squared() < 1.0 {
-            break;
-        }
-    }
-    v
+/// A uniformly random point inside the unit disk, drawn directly via
+/// `rand_distr::UnitDisc` instead of the old reject-until-inside loop.
+fn random_in_unit_disk(rng: &mut dyn RngCore) -> Vec3 {
+    let [x, y]: [Elem; 2] = UnitDisc.sample(rng);
+    Vec3(x, y, 0.0)
 }
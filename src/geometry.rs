@@ -1,10 +1,89 @@
-use std::rc::Rc;
-use crate::vec::*;
 use crate::material::*;
+use crate::vec::*;
+use std::f64::consts::PI;
+use std::sync::Arc;
 
 /// Abstracts away an object which can be hit by a ray.
 pub trait Hitable {
     fn hit(&self, ray: &Ray, tmin: Elem, tmax: Elem) -> Option<HitRecord>;
+
+    /// The smallest axis-aligned box enclosing this object, or
+    /// `None` if it has no finite extent. Used to build a `BvhNode`.
+    fn bounding_box(&self) -> Option<Aabb>;
+}
+
+impl<T: Hitable + ?Sized> Hitable for Box<T> {
+    fn hit(&self, ray: &Ray, tmin: Elem, tmax: Elem) -> Option<HitRecord> {
+        (**self).hit(ray, tmin, tmax)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        (**self).bounding_box()
+    }
+}
+
+/// An axis-aligned bounding box, used by `BvhNode` to cheaply reject
+/// rays that cannot possibly hit the objects it encloses.
+#[derive(Debug, Copy, Clone)]
+pub struct Aabb {
+    pub min: Vec3,
+    pub max: Vec3,
+}
+
+impl Aabb {
+    pub fn new(min: Vec3, max: Vec3) -> Aabb {
+        Aabb { min, max }
+    }
+
+    /// Slab test: for each axis, intersect the ray's parametric
+    /// interval with the box's interval along that axis: the box is
+    /// hit only if the intersection of all three intervals is
+    /// non-empty.
+    pub fn hit(&self, ray: &Ray, t_min: Elem, t_max: Elem) -> bool {
+        let mut t_min = t_min;
+        let mut t_max = t_max;
+
+        for axis in 0..3 {
+            let inv_d = 1.0 / component(ray.direction(), axis);
+            let mut t0 = (component(self.min, axis) - component(ray.origin(), axis)) * inv_d;
+            let mut t1 = (component(self.max, axis) - component(ray.origin(), axis)) * inv_d;
+            if inv_d < 0.0 {
+                std::mem::swap(&mut t0, &mut t1);
+            }
+
+            t_min = t_min.max(t0);
+            t_max = t_max.min(t1);
+            if t_max <= t_min {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    /// The smallest box enclosing both `a` and `b`.
+    pub fn surrounding(a: &Aabb, b: &Aabb) -> Aabb {
+        let min = Vec3(
+            a.min.x().min(b.min.x()),
+            a.min.y().min(b.min.y()),
+            a.min.z().min(b.min.z()),
+        );
+        let max = Vec3(
+            a.max.x().max(b.max.x()),
+            a.max.y().max(b.max.y()),
+            a.max.z().max(b.max.z()),
+        );
+        Aabb::new(min, max)
+    }
+}
+
+/// Picks the `x`/`y`/`z` component of `v` by index (0/1/2).
+pub(crate) fn component(v: Vec3, axis: u8) -> Elem {
+    match axis {
+        0 => v.x(),
+        1 => v.y(),
+        _ => v.z(),
+    }
 }
 
 /// Packs together all the details of a ray hitting an object at
@@ -19,8 +98,14 @@ pub struct HitRecord {
     /// Normal unit vector at the hit point.
     pub normal: Vec3,
 
+    /// Texture `u` coordinate at the hit point.
+    pub u: Elem,
+
+    /// Texture `v` coordinate at the hit point.
+    pub v: Elem,
+
     /// Reference to material at hit point.
-    pub material: Rc<dyn Material>,
+    pub material: Arc<dyn Material>,
 }
 
 /// Defines a ray of light by using origin (a point) and a direction
@@ -28,11 +113,18 @@ pub struct HitRecord {
 pub struct Ray {
     from: Vec3,
     to: Vec3,
+    time: Elem,
+    wavelength: Option<Elem>,
 }
 
 impl Ray {
-    pub fn new(from: Vec3, to: Vec3) -> Ray {
-        Ray { from, to }
+    pub fn new(from: Vec3, to: Vec3, time: Elem) -> Ray {
+        Ray {
+            from,
+            to,
+            time,
+            wavelength: None,
+        }
     }
 
     pub fn origin(&self) -> Vec3 {
@@ -43,6 +135,40 @@ impl Ray {
         self.to
     }
 
+    /// Time at which this ray was cast (used by time-varying
+    /// geometry such as `MovingSphere`, and by the shutter sampling
+    /// done in `Camera::get_ray`).
+    pub fn time(&self) -> Elem {
+        self.time
+    }
+
+    /// The single wavelength (in nanometers) this ray carries, if
+    /// any. Set by `with_wavelength` once a `Dispersive` material has
+    /// sampled one; `None` for ordinary, achromatic rays.
+    pub fn wavelength(&self) -> Option<Elem> {
+        self.wavelength
+    }
+
+    /// Tags this ray with a single wavelength, in nanometers.
+    pub fn with_wavelength(mut self, wavelength: Elem) -> Ray {
+        self.wavelength = Some(wavelength);
+        self
+    }
+
+    /// Builds a scattered ray leaving `point` toward `direction`,
+    /// carrying this (the incoming) ray's `time()` and `wavelength()`
+    /// forward. Materials should build their outgoing ray through
+    /// this rather than `Ray::new` directly, so a wavelength tagged by
+    /// a `Dispersive` surface survives any later bounce off an
+    /// ordinary material instead of silently reverting to `None`.
+    pub fn derived(&self, point: Vec3, direction: Vec3) -> Ray {
+        let ray = Ray::new(point, direction, self.time);
+        match self.wavelength {
+            Some(wavelength) => ray.with_wavelength(wavelength),
+            None => ray,
+        }
+    }
+
     /// Returns a point corresponding to parameter `t`. Calculated as
     /// `from + t * to`.
     pub fn point_at_parameter(&self, t: Elem) -> Vec3 {
@@ -54,50 +180,158 @@ impl Ray {
 pub struct Sphere {
     pub center: Vec3,
     pub radius: Elem,
-    pub material: Rc<dyn Material>,
+    pub material: Arc<dyn Material>,
 }
 
 impl Hitable for Sphere {
     fn hit(&self, ray: &Ray, t_min: Elem, t_max: Elem) -> Option<HitRecord> {
-        let oc = ray.origin() - self.center;
-        let dir = ray.direction();
-
-        let a = dir.dot(&dir);
-        let b = oc.dot(&dir);
-        let c = oc.dot(&oc) - self.radius * self.radius;
-        let discriminant = b * b - a * c;
-
-        if discriminant > 0.0 {
-            let mk_hit_record = |t| {
-                let p = ray.point_at_parameter(t);
-                Some(HitRecord {
-                    time: t,
-                    point: p,
-                    normal: (p - self.center) / self.radius,
-                    material: Rc::clone(&self.material),
-                })
-            };
-
-            let t_small = (-b - discriminant.sqrt()) / a;
-            let t_big = (-b + discriminant.sqrt()) / a;
-
-            if t_small > t_min && t_small < t_max {
-                mk_hit_record(t_small)
-            } else if t_big > t_min && t_big < t_max {
-                mk_hit_record(t_big)
-            } else {
-                None
-            }
+        sphere_hit(self.center, self.radius, &self.material, ray, t_min, t_max)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let r = Vec3(self.radius, self.radius, self.radius);
+        Some(Aabb::new(self.center - r, self.center + r))
+    }
+}
+
+/// A sphere whose center moves linearly between `center0` (at
+/// `time0`) and `center1` (at `time1`), used to render motion blur.
+/// Everything else about the intersection math is identical to
+/// `Sphere`; only the center is a function of the ray's time.
+pub struct MovingSphere {
+    pub center0: Vec3,
+    pub center1: Vec3,
+    pub time0: Elem,
+    pub time1: Elem,
+    pub radius: Elem,
+    pub material: Arc<dyn Material>,
+}
+
+impl MovingSphere {
+    /// Center of the sphere at a given moment in time, linearly
+    /// interpolated between `center0` and `center1`. A zero-length
+    /// shutter (`time0 == time1`) is treated as a stationary sphere
+    /// at `center0`, rather than dividing by zero.
+    pub fn center(&self, time: Elem) -> Vec3 {
+        if self.time1 == self.time0 {
+            return self.center0;
+        }
+        self.center0
+            + ((time - self.time0) / (self.time1 - self.time0)) * (self.center1 - self.center0)
+    }
+}
+
+impl Hitable for MovingSphere {
+    fn hit(&self, ray: &Ray, t_min: Elem, t_max: Elem) -> Option<HitRecord> {
+        sphere_hit(
+            self.center(ray.time()),
+            self.radius,
+            &self.material,
+            ray,
+            t_min,
+            t_max,
+        )
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let r = Vec3(self.radius, self.radius, self.radius);
+        let box0 = Aabb::new(self.center(self.time0) - r, self.center(self.time0) + r);
+        let box1 = Aabb::new(self.center(self.time1) - r, self.center(self.time1) + r);
+        Some(Aabb::surrounding(&box0, &box1))
+    }
+}
+
+/// Quadratic sphere-intersection test shared by `Sphere` and
+/// `MovingSphere`, parameterized over the (possibly time-dependent)
+/// center.
+fn sphere_hit(
+    center: Vec3,
+    radius: Elem,
+    material: &Arc<dyn Material>,
+    ray: &Ray,
+    t_min: Elem,
+    t_max: Elem,
+) -> Option<HitRecord> {
+    let oc = ray.origin() - center;
+    let dir = ray.direction();
+
+    let a = dir.dot(&dir);
+    let b = oc.dot(&dir);
+    let c = oc.dot(&oc) - radius * radius;
+    let discriminant = b * b - a * c;
+
+    if discriminant > 0.0 {
+        let mk_hit_record = |t| {
+            let p = ray.point_at_parameter(t);
+            let normal = (p - center) / radius;
+            let (u, v) = sphere_uv(normal);
+            Some(HitRecord {
+                time: t,
+                point: p,
+                normal,
+                u,
+                v,
+                material: Arc::clone(material),
+            })
+        };
+
+        let t_small = (-b - discriminant.sqrt()) / a;
+        let t_big = (-b + discriminant.sqrt()) / a;
+
+        if t_small > t_min && t_small < t_max {
+            mk_hit_record(t_small)
+        } else if t_big > t_min && t_big < t_max {
+            mk_hit_record(t_big)
         } else {
             None
         }
+    } else {
+        None
     }
 }
 
+/// UV coordinates of a point on the unit sphere, given its outward
+/// normal: `u` wraps around the equator, `v` runs from the south
+/// pole (`v=0`) to the north pole (`v=1`).
+fn sphere_uv(normal: Vec3) -> (Elem, Elem) {
+    let phi = (-normal.z()).atan2(normal.x());
+    let theta = normal.y().asin();
+    let u = 1.0 - (phi + PI) / (2.0 * PI);
+    let v = (theta + PI / 2.0) / PI;
+    (u, v)
+}
+
 impl<T: Hitable> Hitable for [T] {
     fn hit(&self, ray: &Ray, t_min: Elem, t_max: Elem) -> Option<HitRecord> {
         self.iter()
             .filter_map(|h| h.hit(ray, t_min, t_max))
             .min_by(|x, y| x.time.partial_cmp(&y.time).unwrap())
     }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let mut iter = self.iter();
+        let first = iter.next()?.bounding_box()?;
+        iter.try_fold(first, |acc, h| {
+            h.bounding_box().map(|b| Aabb::surrounding(&acc, &b))
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::material::Lambertian;
+
+    #[test]
+    fn moving_sphere_center_handles_zero_length_shutter() {
+        let sphere = MovingSphere {
+            center0: Vec3(1.0, 2.0, 3.0),
+            center1: Vec3(4.0, 5.0, 6.0),
+            time0: 0.5,
+            time1: 0.5,
+            radius: 1.0,
+            material: Arc::new(Lambertian::solid(Vec3(0.5, 0.5, 0.5))),
+        };
+        assert_eq!(sphere.center(0.5), sphere.center0);
+    }
 }
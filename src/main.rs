@@ -1,55 +1,79 @@
 //! This is directly based on Peter Shirley's "Ray Tracing in One
 //! Weekend".
 
-use rand::prelude::*;
+use rand::{Rng, RngCore, SeedableRng};
+use rand_pcg::Pcg32;
 use rayon::prelude::*;
 use rt::MaterialResponse::*;
 use rt::*;
-use std::f32;
 use std::sync::Arc;
 
-fn color<T: Hitable + ?Sized>(ray: &Ray, world: &T, depth: u32) -> Vec3 {
-    if let Some(hit) = world.hit(ray, 0.001, f32::MAX) {
+fn color<T: Hitable + ?Sized>(
+    ray: &Ray,
+    world: &T,
+    background: Vec3,
+    depth: u32,
+    rng: &mut dyn RngCore,
+) -> Vec3 {
+    if let Some(hit) = world.hit(ray, 0.001, Elem::MAX) {
+        let emission = tint(hit.material.emitted(hit.u, hit.v, hit.point), ray);
         if depth < 50 {
-            match hit.material.scatter(ray, &hit) {
-                Absorbed => Vec3::zero(),
-                Scattered { attenuation, ray } => attenuation * color(&ray, world, depth + 1),
+            match hit.material.scatter(ray, &hit, rng) {
+                Absorbed => emission,
+                Scattered { attenuation, ray } => {
+                    emission + attenuation * color(&ray, world, background, depth + 1, rng)
+                }
             }
         } else {
-            Vec3::zero()
+            emission
         }
     } else {
-        // Draw gradient background.
-        let unit_direction = Vec3::unit_vector(ray.direction());
-        let t = 0.5 * (unit_direction.y() + 1.0);
-        let white = Vec3(1.0, 1.0, 1.0);
-        let blue = Vec3(0.5, 0.7, 1.0);
-
-        // Interpolate between white and "blue".
-        (1.0 - t) * white + t * blue
+        tint(background, ray)
+    }
+}
+
+/// Applies a `Dispersive` ray's wavelength color, if it has one, to a
+/// terminal radiance value (background or emission). Doing this only
+/// where a path actually ends means the wavelength's color is applied
+/// exactly once per path, no matter how many dispersive surfaces it
+/// crossed to get there.
+fn tint(radiance: Vec3, ray: &Ray) -> Vec3 {
+    match ray.wavelength() {
+        Some(wavelength) => radiance * wavelength_to_rgb(wavelength),
+        None => radiance,
     }
 }
 
-fn random_scene() -> Vec<Sphere> {
-    let mut scene = Vec::with_capacity(500);
-    scene.push(Sphere {
+fn random_scene() -> Vec<Box<dyn Hitable + Send + Sync>> {
+    let mut scene: Vec<Box<dyn Hitable + Send + Sync>> = Vec::with_capacity(500);
+    let ground_checker =
+        CheckerTexture::solid(Vec3(0.2, 0.3, 0.1), Vec3(0.9, 0.9, 0.9), 10.0);
+    scene.push(Box::new(Sphere {
         center: Vec3(0.0, -1000.0, 0.0),
         radius: 1000.0,
-        material: Arc::new(Lambertian::new(Vec3(0.5, 0.5, 0.5))),
-    });
+        material: Arc::new(Lambertian::new(Box::new(ground_checker))),
+    }));
 
     let mut rng = rand::thread_rng();
     for a in -11..11_i16 {
         for b in -11..11_i16 {
-            let choose_mat: f32 = rng.gen();
-            let mut rnd = || rng.gen::<f32>();
-            let center = Vec3(f32::from(a) + 0.9 * rnd(), 0.2, f32::from(b) + 0.9 * rnd());
+            let choose_mat: Elem = rng.gen();
+            let mut rnd = || rng.gen::<Elem>();
+            let center = Vec3(Elem::from(a) + 0.9 * rnd(), 0.2, Elem::from(b) + 0.9 * rnd());
             if (center - Vec3(4.0, 0.2, 0.0)).length() > 0.9 {
-                let material: Arc<dyn Material>;
                 if choose_mat < 0.8 {
-                    // Diffuse.
+                    // Diffuse: bounces vertically over the shutter
+                    // interval, which is what produces motion blur.
                     let albedo = Vec3(rnd() * rnd(), rnd() * rnd(), rnd() * rnd());
-                    material = Arc::new(Lambertian::new(albedo));
+                    let material = Arc::new(Lambertian::solid(albedo));
+                    scene.push(Box::new(MovingSphere {
+                        center0: center,
+                        center1: center + Vec3(0.0, 0.5 * rnd(), 0.0),
+                        time0: 0.0,
+                        time1: 1.0,
+                        radius: 0.2,
+                        material,
+                    }));
                 } else if choose_mat < 0.95 {
                     // Metal.
                     let albedo = Vec3(
@@ -58,39 +82,51 @@ fn random_scene() -> Vec<Sphere> {
                         0.5 * (1.0 + rnd()),
                     );
                     let fuzz = 0.5 * rnd();
-                    material = Arc::new(Metal::new(albedo, fuzz));
+                    let material = Arc::new(Metal::new(albedo, fuzz));
+                    scene.push(Box::new(Sphere {
+                        center,
+                        radius: 0.2,
+                        material,
+                    }));
                 } else {
                     // Glass.
                     let refr_index = 1.5;
-                    material = Arc::new(Dielectric::new(refr_index));
+                    let material = Arc::new(Dielectric::new(refr_index));
+                    scene.push(Box::new(Sphere {
+                        center,
+                        radius: 0.2,
+                        material,
+                    }));
                 }
-
-                scene.push(Sphere {
-                    center,
-                    radius: 0.2,
-                    material,
-                });
             }
         }
     }
 
-    scene.push(Sphere {
+    scene.push(Box::new(Sphere {
         center: Vec3(0.0, 1.0, 0.0),
         radius: 1.0,
         material: Arc::new(Dielectric::new(1.5)),
-    });
+    }));
 
-    scene.push(Sphere {
+    scene.push(Box::new(Sphere {
         center: Vec3(-4.0, 1.0, 0.0),
         radius: 1.0,
-        material: Arc::new(Lambertian::new(Vec3(0.4, 0.2, 0.1))),
-    });
+        material: Arc::new(Lambertian::solid(Vec3(0.4, 0.2, 0.1))),
+    }));
 
-    scene.push(Sphere {
+    scene.push(Box::new(Sphere {
         center: Vec3(4.0, 1.0, 0.0),
         radius: 1.0,
         material: Arc::new(Metal::new(Vec3(0.7, 0.6, 0.5), 0.0)),
-    });
+    }));
+
+    // A small dispersive sphere, so the spectral rendering path is
+    // actually exercised by the demo scene.
+    scene.push(Box::new(Sphere {
+        center: Vec3(2.0, 0.4, 2.5),
+        radius: 0.4,
+        material: Arc::new(Dispersive::flint_glass()),
+    }));
 
     scene
 }
@@ -103,9 +139,10 @@ fn main() {
 
     println!("P3 {} {} 255", nx, ny);
 
-    let world = random_scene();
+    let world = BvhNode::new(random_scene());
+    let background = Vec3(0.5, 0.7, 1.0);
 
-    let aspect = f32::from(nx) / f32::from(ny);
+    let aspect = Elem::from(nx) / Elem::from(ny);
     let look_from = Vec3(13.0, 2.0, 3.0);
     let look_at = Vec3(0.0, 0.0, 0.0);
     let camera = Camera::new(CameraSettings {
@@ -116,23 +153,32 @@ fn main() {
         aspect,
         aperture: 0.1,
         focus_dist: 10.0,
+        time0: 0.0,
+        time1: 1.0,
     });
 
+    // Each row gets its own PCG stream, seeded from its index, so the
+    // samples traced for a row are reproducible from `SEED` regardless
+    // of which thread ends up processing which row. The scene itself
+    // is still laid out by `random_scene` from the thread-local
+    // generator, so it differs from run to run.
+    const SEED: u64 = 0xC0FF_EE15_5EED_2021;
+
     // for j in (0..ny).rev() {
     (0..ny).into_par_iter().for_each(|j| {
-        let mut rng = rand::thread_rng();
+        let mut rng = Pcg32::seed_from_u64(SEED.wrapping_add(j as u64));
         for i in 0..nx {
             let mut col = Vec3::zero();
 
             // Antialiasing by averaging of random samples.
             for _ in 0..ns {
-                let u = (f32::from(i) + rng.gen::<f32>()) / f32::from(nx);
-                let v = (f32::from(j) + rng.gen::<f32>()) / f32::from(ny);
-                let r = camera.get_ray(u, v);
-                col += color(&r, &world[..], 0);
+                let u = (Elem::from(i) + rng.gen::<Elem>()) / Elem::from(nx);
+                let v = (Elem::from(j) + rng.gen::<Elem>()) / Elem::from(ny);
+                let r = camera.get_ray(u, v, &mut rng);
+                col += color(&r, &world, background, 0, &mut rng);
             }
 
-            col /= f32::from(ns);
+            col /= Elem::from(ns);
             col.sqrt_coords(); // Basic gamma correction.
             col *= 255.99;
 
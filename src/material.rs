@@ -1,11 +1,28 @@
 use crate::geometry::*;
+use crate::spectrum::*;
+use crate::texture::*;
 use crate::vec::*;
-use rand::prelude::*;
+use rand::{Rng, RngCore};
+use rand_distr::{Distribution, UnitSphere};
 
 /// Abstracts away details of materials affecting how the rays
-/// scatter.
-pub trait Material {
-    fn scatter(&self, ray: &Ray, hr: &HitRecord) -> MaterialResponse;
+/// scatter. `Send + Sync` so scenes can be shared across the
+/// per-scanline render threads.
+pub trait Material: Send + Sync {
+    /// `rng` is the caller's (deterministic, seedable) random
+    /// source, threaded through so every scatter decision of a render
+    /// can be reproduced from a single seed.
+    fn scatter(&self, ray: &Ray, hr: &HitRecord, rng: &mut dyn RngCore) -> MaterialResponse;
+
+    /// Radiance emitted by the material itself at `(u, v, point)`,
+    /// independent of any incoming ray. Defaults to black; only
+    /// light sources such as `DiffuseLight` need to override it. The
+    /// UV/point parameters let a future light source pattern its
+    /// emission (e.g. an emissive texture) rather than always
+    /// glowing a single flat color.
+    fn emitted(&self, _u: Elem, _v: Elem, _point: Vec3) -> Vec3 {
+        Vec3::zero()
+    }
 }
 
 /// How the material responses to a ray.
@@ -17,24 +34,29 @@ pub enum MaterialResponse {
 /// Lambertian defines diffused materials which reflect light
 /// randomly.
 pub struct Lambertian {
-    albedo: Vec3,
+    albedo: Box<dyn Texture>,
 }
 
 impl Lambertian {
-    /// Takes `albedo` parameter which in fact defines object's own
-    /// color. Hitting rays will be attenuated based on this
-    /// parameter.
-    pub fn new(albedo: Vec3) -> Self {
+    /// Takes an `albedo` texture, sampled at the hit point, which
+    /// defines the object's own color. Hitting rays will be
+    /// attenuated based on it.
+    pub fn new(albedo: Box<dyn Texture>) -> Self {
         Lambertian { albedo }
     }
+
+    /// Convenience constructor for a flat, single-color surface.
+    pub fn solid(color: Vec3) -> Self {
+        Lambertian::new(Box::new(SolidColor::new(color)))
+    }
 }
 
 impl Material for Lambertian {
-    fn scatter(&self, _ray: &Ray, hr: &HitRecord) -> MaterialResponse {
-        let target = hr.point + hr.normal + random_in_unit_sphere();
-        let sc_ray = Ray::new(hr.point, target - hr.point);
+    fn scatter(&self, ray: &Ray, hr: &HitRecord, rng: &mut dyn RngCore) -> MaterialResponse {
+        let target = hr.point + hr.normal + random_in_unit_sphere(rng);
+        let sc_ray = ray.derived(hr.point, target - hr.point);
         MaterialResponse::Scattered {
-            attenuation: self.albedo,
+            attenuation: self.albedo.value(hr.u, hr.v, hr.point),
             ray: sc_ray,
         }
     }
@@ -59,12 +81,9 @@ impl Metal {
 }
 
 impl Material for Metal {
-    fn scatter(&self, ray: &Ray, hr: &HitRecord) -> MaterialResponse {
+    fn scatter(&self, ray: &Ray, hr: &HitRecord, rng: &mut dyn RngCore) -> MaterialResponse {
         let reflected_dir = reflect(Vec3::unit_vector(ray.direction()), hr.normal);
-        let scattered = Ray::new(
-            hr.point,
-            reflected_dir + self.fuzz * random_in_unit_sphere(),
-        );
+        let scattered = ray.derived(hr.point, reflected_dir + self.fuzz * random_in_unit_sphere(rng));
         if scattered.direction().dot(&hr.normal) > 0.0 {
             MaterialResponse::Scattered {
                 attenuation: self.albedo,
@@ -90,7 +109,7 @@ impl Dielectric {
 }
 
 impl Material for Dielectric {
-    fn scatter(&self, ray: &Ray, hr: &HitRecord) -> MaterialResponse {
+    fn scatter(&self, ray: &Ray, hr: &HitRecord, rng: &mut dyn RngCore) -> MaterialResponse {
         let d1 = Vec3::dot2(hr.normal, ray.direction());
         let back = d1 > 0.0;
         let (outward_normal, ni_over_nt, cosine) = if back {
@@ -101,14 +120,14 @@ impl Material for Dielectric {
             (hr.normal, 1.0 / self.refraction_index, cos)
         };
 
-        let reflected_ray = Ray::new(hr.point, reflect(ray.direction(), hr.normal));
+        let reflected_ray = ray.derived(hr.point, reflect(ray.direction(), hr.normal));
         let sc_ray = match refract(ray.direction(), outward_normal, ni_over_nt) {
             Some(refracted) => {
                 let reflect_prob = schlick(cosine, self.refraction_index);
-                if rand::random::<Elem>() < reflect_prob {
+                if rng.gen::<Elem>() < reflect_prob {
                     reflected_ray
                 } else {
-                    Ray::new(hr.point, refracted)
+                    ray.derived(hr.point, refracted)
                 }
             }
             None => reflected_ray,
@@ -121,20 +140,133 @@ impl Material for Dielectric {
     }
 }
 
+/// A dispersive dielectric whose refraction index depends on
+/// wavelength, per Cauchy's equation `n(lambda) = a + b / lambda^2`
+/// (`lambda` in micrometers). Unlike `Dielectric`, a ray hitting this
+/// material is rendered in "spectral" mode: if it doesn't already
+/// carry a wavelength, one is sampled uniformly from the visible
+/// band and tagged onto the scattered ray, so it keeps the same
+/// wavelength (and hence the same refraction index) through any
+/// further bounces off other dispersive surfaces. `scatter` itself
+/// attenuates by white (a ray crosses the surface at least twice —
+/// entry and exit — so tinting at every bounce would apply the
+/// wavelength's color more than once); the caller is responsible for
+/// converting the wavelength to a color exactly once, at wherever the
+/// path actually terminates (`main::color` does this).
+pub struct Dispersive {
+    a: Elem,
+    b: Elem,
+}
+
+impl Dispersive {
+    /// Takes Cauchy's equation coefficients directly.
+    pub fn new(a: Elem, b: Elem) -> Dispersive {
+        Dispersive { a, b }
+    }
+
+    /// Convenience constructor with coefficients typical of a
+    /// flint-glass-like dispersion.
+    pub fn flint_glass() -> Dispersive {
+        Dispersive::new(1.5046, 0.00420)
+    }
+}
+
+impl Material for Dispersive {
+    fn scatter(&self, ray: &Ray, hr: &HitRecord, rng: &mut dyn RngCore) -> MaterialResponse {
+        let wavelength = ray.wavelength().unwrap_or_else(|| sample_wavelength(rng));
+        let lambda_um = wavelength / 1000.0;
+        let refraction_index = self.a + self.b / (lambda_um * lambda_um);
+
+        let d1 = Vec3::dot2(hr.normal, ray.direction());
+        let back = d1 > 0.0;
+        let (outward_normal, ni_over_nt, cosine) = if back {
+            let cos = refraction_index * d1 / ray.direction().length();
+            (-hr.normal, refraction_index, cos)
+        } else {
+            let cos = -d1 / ray.direction().length();
+            (hr.normal, 1.0 / refraction_index, cos)
+        };
+
+        let reflected_ray = ray
+            .derived(hr.point, reflect(ray.direction(), hr.normal))
+            .with_wavelength(wavelength);
+        let sc_ray = match refract(ray.direction(), outward_normal, ni_over_nt) {
+            Some(refracted) => {
+                let reflect_prob = schlick(cosine, refraction_index);
+                if rng.gen::<Elem>() < reflect_prob {
+                    reflected_ray
+                } else {
+                    ray.derived(hr.point, refracted).with_wavelength(wavelength)
+                }
+            }
+            None => reflected_ray,
+        };
+
+        MaterialResponse::Scattered {
+            attenuation: Vec3::ones(),
+            ray: sc_ray,
+        }
+    }
+}
+
+/// A light source: emits a constant radiance and scatters no rays.
+pub struct DiffuseLight {
+    emit: Vec3,
+}
+
+impl DiffuseLight {
+    /// Takes the color (and intensity) of light emitted by the
+    /// surface.
+    pub fn new(emit: Vec3) -> Self {
+        DiffuseLight { emit }
+    }
+}
+
+impl Material for DiffuseLight {
+    fn scatter(&self, _ray: &Ray, _hr: &HitRecord, _rng: &mut dyn RngCore) -> MaterialResponse {
+        MaterialResponse::Absorbed
+    }
+
+    fn emitted(&self, _u: Elem, _v: Elem, _point: Vec3) -> Vec3 {
+        self.emit
+    }
+}
+
+/// Scatters incoming rays in a uniformly random direction,
+/// regardless of where they came from. Used as the phase function of
+/// `ConstantMedium` to model light bouncing around inside smoke, fog
+/// or clouds.
+pub struct Isotropic {
+    albedo: Box<dyn Texture>,
+}
+
+impl Isotropic {
+    pub fn new(albedo: Box<dyn Texture>) -> Self {
+        Isotropic { albedo }
+    }
+}
+
+impl Material for Isotropic {
+    fn scatter(&self, ray: &Ray, hr: &HitRecord, rng: &mut dyn RngCore) -> MaterialResponse {
+        MaterialResponse::Scattered {
+            attenuation: self.albedo.value(hr.u, hr.v, hr.point),
+            ray: ray.derived(hr.point, random_in_unit_sphere(rng)),
+        }
+    }
+}
+
 //
 // Helper functions.
 //
 
-fn random_in_unit_sphere() -> Vec3 {
-    let mut v;
-    let mut rng = rand::thread_rng();
-    loop {
-        v = 2.0 * Vec3(rng.gen(), rng.gen(), rng.gen()) - Vec3::ones();
-        if v.length_squared() < 1.0 {
-            break;
-        }
-    }
-    v
+/// A uniformly random point inside the unit ball. Draws a point on
+/// the unit sphere's surface and scales it by `cbrt(u)` for a
+/// uniform `u` in `[0, 1)`, which distributes volume correctly
+/// without the rejection loop this used to be.
+fn random_in_unit_sphere(rng: &mut dyn RngCore) -> Vec3 {
+    let [x, y, z]: [Elem; 3] = UnitSphere.sample(rng);
+    let radius: Elem = rng.gen::<Elem>().cbrt();
+    Vec3(x, y, z) * radius
 }
 
 fn reflect(v: Vec3, normal: Vec3) -> Vec3 {
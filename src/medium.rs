@@ -0,0 +1,81 @@
+use crate::geometry::*;
+use crate::material::*;
+use crate::texture::{SolidColor, Texture};
+use crate::vec::*;
+use std::sync::Arc;
+
+/// A volume of constant-density participating media (smoke, fog,
+/// clouds) filling the space enclosed by `boundary`. Rays that pass
+/// through it scatter at a random depth governed by `density`,
+/// rather than only at the boundary's surface.
+pub struct ConstantMedium {
+    boundary: Box<dyn Hitable + Send + Sync>,
+    density: Elem,
+    phase_function: Arc<dyn Material>,
+}
+
+impl ConstantMedium {
+    /// Convenience constructor for a flat, single-color medium.
+    pub fn new(boundary: Box<dyn Hitable + Send + Sync>, density: Elem, albedo: Vec3) -> Self {
+        ConstantMedium::with_texture(boundary, density, Box::new(SolidColor::new(albedo)))
+    }
+
+    /// As `new`, but takes an arbitrary `albedo` texture instead of a
+    /// flat color, e.g. to fade a cloud's density-driven scattering
+    /// color across its volume.
+    pub fn with_texture(
+        boundary: Box<dyn Hitable + Send + Sync>,
+        density: Elem,
+        albedo: Box<dyn Texture>,
+    ) -> Self {
+        ConstantMedium {
+            boundary,
+            density,
+            phase_function: Arc::new(Isotropic::new(albedo)),
+        }
+    }
+}
+
+impl Hitable for ConstantMedium {
+    fn hit(&self, ray: &Ray, t_min: Elem, t_max: Elem) -> Option<HitRecord> {
+        let mut entry = self.boundary.hit(ray, -Elem::MAX, Elem::MAX)?;
+        let mut exit = self.boundary.hit(ray, entry.time + 0.0001, Elem::MAX)?;
+
+        if entry.time < t_min {
+            entry.time = t_min;
+        }
+        if exit.time > t_max {
+            exit.time = t_max;
+        }
+        if entry.time >= exit.time {
+            return None;
+        }
+
+        let ray_length = ray.direction().length();
+        let distance_inside = (exit.time - entry.time) * ray_length;
+        // Drawn from the thread-local generator, not the caller's
+        // seeded one, so where a ray scatters inside the medium is not
+        // reproducible from a render's seed.
+        let hit_dist = -(1.0 / self.density) * rand::random::<Elem>().ln();
+
+        if hit_dist >= distance_inside {
+            return None;
+        }
+
+        let time = entry.time + hit_dist / ray_length;
+        Some(HitRecord {
+            time,
+            point: ray.point_at_parameter(time),
+            // The normal doesn't matter inside a volume (scattering
+            // is isotropic), so any unit vector will do.
+            normal: Vec3::one_x(),
+            u: 0.0,
+            v: 0.0,
+            material: Arc::clone(&self.phase_function),
+        })
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.boundary.bounding_box()
+    }
+}
@@ -0,0 +1,91 @@
+use crate::vec::*;
+use rand::{Rng, RngCore};
+
+/// Visible-light wavelength range (nanometers) from which a single
+/// wavelength is sampled per dispersive-refraction ray.
+const VISIBLE_MIN_NM: Elem = 380.0;
+const VISIBLE_MAX_NM: Elem = 750.0;
+
+/// Draws a wavelength, in nanometers, uniformly from the visible
+/// band; it is not weighted toward any part of the spectrum.
+pub fn sample_wavelength(rng: &mut dyn RngCore) -> Elem {
+    rng.gen_range(VISIBLE_MIN_NM..VISIBLE_MAX_NM)
+}
+
+/// Converts a single wavelength (in nanometers) to a linear sRGB
+/// color, via the CIE 1931 color-matching functions (approximated as
+/// a small sum of Gaussian lobes, per Wyman/Sloan/Shirley) and the
+/// standard XYZ -> linear sRGB matrix. This is what lets a path
+/// that crossed a `Dispersive` dielectric be colored by the single
+/// wavelength its ray carries (applied where the path ends, not by
+/// `Dispersive::scatter` itself).
+pub fn wavelength_to_rgb(wavelength: Elem) -> Vec3 {
+    let x = gaussian_sum(
+        wavelength,
+        &[
+            (1.056, 599.8, 37.9, 31.0),
+            (0.362, 442.0, 16.0, 26.7),
+            (-0.065, 501.1, 20.4, 26.2),
+        ],
+    );
+    let y = gaussian_sum(
+        wavelength,
+        &[(0.821, 568.8, 46.9, 40.5), (0.286, 530.9, 16.3, 31.1)],
+    );
+    let z = gaussian_sum(
+        wavelength,
+        &[(1.217, 437.0, 11.8, 36.0), (0.681, 459.0, 26.0, 13.8)],
+    );
+
+    let r = 3.2406 * x - 1.5372 * y - 0.4986 * z;
+    let g = -0.9689 * x + 1.8758 * y + 0.0415 * z;
+    let b = 0.0557 * x - 0.2040 * y + 1.0570 * z;
+
+    // The matching functions above aren't normalized to stay within
+    // [0, 1], so clamp rather than let a saturated wavelength (e.g.
+    // green, around 530nm) blow past the gamut every other material's
+    // albedo lives in.
+    Vec3(
+        r.clamp(0.0, 1.0),
+        g.clamp(0.0, 1.0),
+        b.clamp(0.0, 1.0),
+    )
+}
+
+/// Sum of asymmetric Gaussian lobes, each `(amplitude, mean,
+/// sigma_left, sigma_right)`, evaluated at `x`.
+fn gaussian_sum(x: Elem, lobes: &[(Elem, Elem, Elem, Elem)]) -> Elem {
+    lobes
+        .iter()
+        .map(|&(amplitude, mean, sigma_left, sigma_right)| {
+            let sigma = if x < mean { sigma_left } else { sigma_right };
+            amplitude * (-0.5 * ((x - mean) / sigma).powi(2)).exp()
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn wavelength_to_rgb_stays_in_unit_range() {
+        let mut wavelength = VISIBLE_MIN_NM;
+        while wavelength <= VISIBLE_MAX_NM {
+            let rgb = wavelength_to_rgb(wavelength);
+            assert!(rgb.r() >= 0.0 && rgb.r() <= 1.0);
+            assert!(rgb.g() >= 0.0 && rgb.g() <= 1.0);
+            assert!(rgb.b() >= 0.0 && rgb.b() <= 1.0);
+            wavelength += 5.0;
+        }
+    }
+
+    #[test]
+    fn sample_wavelength_stays_in_visible_band() {
+        let mut rng = rand::thread_rng();
+        for _ in 0..100 {
+            let w = sample_wavelength(&mut rng);
+            assert!((VISIBLE_MIN_NM..VISIBLE_MAX_NM).contains(&w));
+        }
+    }
+}
@@ -0,0 +1,100 @@
+use crate::vec::*;
+
+/// Maps a hit point and its UV parameterization to a color. This is
+/// what lets a surface carry a pattern or an image instead of a
+/// single flat albedo.
+pub trait Texture: Send + Sync {
+    fn value(&self, u: Elem, v: Elem, p: Vec3) -> Vec3;
+}
+
+/// A texture that always returns the same color, regardless of
+/// where it's sampled.
+pub struct SolidColor {
+    color: Vec3,
+}
+
+impl SolidColor {
+    pub fn new(color: Vec3) -> Self {
+        SolidColor { color }
+    }
+}
+
+impl Texture for SolidColor {
+    fn value(&self, _u: Elem, _v: Elem, _p: Vec3) -> Vec3 {
+        self.color
+    }
+}
+
+/// A 3D checkerboard pattern alternating between two sub-textures,
+/// based on the sign of `sin(scale*x)*sin(scale*y)*sin(scale*z)`.
+pub struct CheckerTexture {
+    odd: Box<dyn Texture>,
+    even: Box<dyn Texture>,
+    scale: Elem,
+}
+
+impl CheckerTexture {
+    pub fn new(odd: Box<dyn Texture>, even: Box<dyn Texture>, scale: Elem) -> Self {
+        CheckerTexture { odd, even, scale }
+    }
+
+    /// Convenience constructor for a checkerboard of two flat colors.
+    pub fn solid(odd: Vec3, even: Vec3, scale: Elem) -> Self {
+        CheckerTexture::new(Box::new(SolidColor::new(odd)), Box::new(SolidColor::new(even)), scale)
+    }
+}
+
+impl Texture for CheckerTexture {
+    fn value(&self, u: Elem, v: Elem, p: Vec3) -> Vec3 {
+        let sines =
+            (self.scale * p.x()).sin() * (self.scale * p.y()).sin() * (self.scale * p.z()).sin();
+        if sines < 0.0 {
+            self.odd.value(u, v, p)
+        } else {
+            self.even.value(u, v, p)
+        }
+    }
+}
+
+/// A texture backed by a loaded RGB image, sampled by clamped UV
+/// coordinates. `data` is packed as `width * height` pixels, 3 bytes
+/// (R, G, B) each, in row-major order starting at the top-left
+/// pixel.
+pub struct ImageTexture {
+    data: Vec<u8>,
+    width: usize,
+    height: usize,
+}
+
+impl ImageTexture {
+    pub fn new(data: Vec<u8>, width: usize, height: usize) -> Self {
+        ImageTexture {
+            data,
+            width,
+            height,
+        }
+    }
+}
+
+impl Texture for ImageTexture {
+    fn value(&self, u: Elem, v: Elem, _p: Vec3) -> Vec3 {
+        if self.width == 0 || self.height == 0 {
+            // No image data: fall back to a garish color so the
+            // mistake is obvious rather than silent.
+            return Vec3(0.0, 1.0, 1.0);
+        }
+
+        let u = u.clamp(0.0, 1.0);
+        let v = 1.0 - v.clamp(0.0, 1.0);
+
+        let i = ((u * self.width as Elem) as usize).min(self.width - 1);
+        let j = ((v * self.height as Elem) as usize).min(self.height - 1);
+
+        let offset = 3 * (j * self.width + i);
+        Vec3(
+            self.data[offset] as Elem / 255.0,
+            self.data[offset + 1] as Elem / 255.0,
+            self.data[offset + 2] as Elem / 255.0,
+        )
+    }
+}